@@ -0,0 +1,148 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+// a map of the forest, parsed once up front.
+// the pattern repeats forever to the left and right,
+// so columns wrap around but rows don't
+pub struct Forest {
+    // one byte per square, row after row: 1 for a tree, 0 for open ground
+    cells: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+#[derive(Debug)]
+pub enum ForestError {
+    MissingFile {
+        path: String,
+        source: io::Error,
+    },
+    UnreadableRow {
+        row: usize,
+        source: io::Error,
+    },
+    // anything that isn't open ground (.) or a tree (#)
+    InvalidSquare {
+        row: usize,
+        column: usize,
+        square: char,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ForestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // rows and columns are 0-indexed everywhere else,
+        // but people read files from line 1, column 1
+        match self {
+            ForestError::MissingFile { path, source } => {
+                write!(f, "couldn't open {}: {}", path, source)
+            }
+            ForestError::UnreadableRow { row, source } => {
+                write!(f, "couldn't read line {}: {}", row + 1, source)
+            }
+            ForestError::InvalidSquare {
+                row,
+                column,
+                square,
+            } => write!(
+                f,
+                "line {}, column {}: {:?} isn't open ground (.) or a tree (#)",
+                row + 1,
+                column + 1,
+                square
+            ),
+            ForestError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} is {} squares wide, but the forest is {} squares wide",
+                row + 1,
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ForestError {}
+
+impl Forest {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Forest, ForestError> {
+        let file = File::open(&path).map_err(|source| ForestError::MissingFile {
+            path: path.as_ref().display().to_string(),
+            source,
+        })?;
+        Forest::from_reader(BufReader::new(file))
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Forest, ForestError> {
+        let mut cells = vec![];
+        let mut width = 0;
+        let mut height = 0;
+
+        for (row, line) in reader.lines().enumerate() {
+            let line = line.map_err(|source| ForestError::UnreadableRow { row, source })?;
+            let row_start = cells.len();
+            for (column, square) in line.chars().enumerate() {
+                match square {
+                    '.' => cells.push(0),
+                    '#' => cells.push(1),
+                    square => {
+                        return Err(ForestError::InvalidSquare {
+                            row,
+                            column,
+                            square,
+                        })
+                    }
+                }
+            }
+
+            // the first row decides how wide the forest is.
+            // every other row has to match, or wrapping stops making sense
+            let found = cells.len() - row_start;
+            if row == 0 {
+                width = found;
+            } else if found != width {
+                return Err(ForestError::RaggedRow {
+                    row,
+                    expected: width,
+                    found,
+                });
+            }
+            height += 1;
+        }
+
+        Ok(Forest {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // x can be any column, including negative ones, since the forest repeats.
+    // anything past the bottom of the map is open ground
+    pub fn is_tree(&self, x: i64, y: usize) -> bool {
+        if y >= self.height || self.width == 0 {
+            return false;
+        }
+        let x = x.rem_euclid(self.width as i64) as usize;
+        self.cells[y * self.width + x] == 1
+    }
+}
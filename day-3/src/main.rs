@@ -1,97 +1,388 @@
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Lines};
-use std::iter::Enumerate;
-use std::path::Path;
-
-fn check_tree_at_index(index: usize, tree_row: &str) -> bool {
-    match tree_row.chars().nth(index) {
-        Some(ascii) => ascii == '#',
-        None => false,
+mod forest;
+
+use forest::Forest;
+use std::env;
+use std::fs::read_to_string;
+use std::process;
+
+const USAGE: &str = "usage: day-3 [FOREST_FILE] [--slope RIGHT,DOWN]... [--slopes-file PATH] \
+                     [--start COLUMN,ROW] [--search [--max-right N] [--max-down N]] [--render]";
+
+// the slopes from the puzzle, used when none are passed in
+const DEFAULT_SLOPES: [(i64, u32); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+#[derive(Clone)]
+struct Slope {
+    // negative steps move left through the forest
+    x: i64,
+    y: u32,
+}
+
+struct Start {
+    // any column works, since the forest repeats to the left and right
+    x: i64,
+    y: u32,
+}
+
+struct Options {
+    forest_file: String,
+    slopes: Vec<Slope>,
+    start: Start,
+    // try every slope up to these steps instead of the ones passed in.
+    // the limits default to the width and height of the forest
+    search: bool,
+    max_right: Option<i64>,
+    max_down: Option<u32>,
+    // print the path each slope takes through the forest
+    render: bool,
+}
+
+fn parse_slope(raw: &str) -> Result<Slope, String> {
+    let mut parts = raw.trim().split(',');
+    let (x, y) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(x), Some(y), None) => (x.trim(), y.trim()),
+        _ => return Err(format!("slope \"{}\" should look like RIGHT,DOWN", raw)),
+    };
+    let x = x
+        .parse::<i64>()
+        .map_err(|_| format!("slope \"{}\" has an invalid right step", raw))?;
+    // parse as signed so we can give a proper error for upward slopes
+    let y = y
+        .parse::<i64>()
+        .map_err(|_| format!("slope \"{}\" has an invalid down step", raw))?;
+    if y <= 0 {
+        return Err(format!(
+            "slope \"{}\" needs to move down at least one row",
+            raw
+        ));
+    }
+    if y > u32::MAX as i64 {
+        return Err(format!("slope \"{}\" moves down too far", raw));
     }
+    Ok(Slope { x, y: y as u32 })
 }
 
-fn count_trees(tree_rows: Enumerate<Lines<BufReader<File>>>) -> u32 {
-    struct Slope {
-        x: u32,
-        y: u32,
+fn parse_start(raw: &str) -> Result<Start, String> {
+    let mut parts = raw.trim().split(',');
+    let (x, y) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(x), Some(y), None) => (x.trim(), y.trim()),
+        _ => return Err(format!("start \"{}\" should look like COLUMN,ROW", raw)),
+    };
+    let x = x
+        .parse::<i64>()
+        .map_err(|_| format!("start \"{}\" has an invalid column", raw))?;
+    let y = y
+        .parse::<u32>()
+        .map_err(|_| format!("start \"{}\" has an invalid row", raw))?;
+    Ok(Start { x, y })
+}
+
+fn parse_limit(flag: &str, raw: Option<String>) -> Result<u32, String> {
+    match raw.map(|raw| raw.parse::<u32>()) {
+        Some(Ok(limit)) if limit > 0 => Ok(limit),
+        _ => Err(format!("{} needs a positive number", flag)),
     }
-    #[derive(Clone)]
-    struct Loc {
-        // our current index in a given row of the forest
-        curr_x: u32,
-        // our current row of the forest
-        curr_y: u32,
-        // how many trees we've found for our give slope
-        tree_count: u32,
+}
+
+fn read_slopes_file(path: &str) -> Result<Vec<Slope>, String> {
+    let raw = read_to_string(path).map_err(|_| format!("couldn't read slopes file {}", path))?;
+    // one RIGHT,DOWN pair per line, skipping blank lines
+    raw.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_slope)
+        .collect()
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut forest_file = None;
+    let mut slopes = vec![];
+    let mut start = Start { x: 0, y: 0 };
+    let mut search = false;
+    let mut max_right = None;
+    let mut max_down = None;
+    let mut render = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--slope" => match args.next() {
+                Some(raw) => slopes.push(parse_slope(&raw)?),
+                None => return Err("--slope needs a RIGHT,DOWN value".to_string()),
+            },
+            "--slopes-file" => match args.next() {
+                Some(path) => slopes.append(&mut read_slopes_file(&path)?),
+                None => return Err("--slopes-file needs a path".to_string()),
+            },
+            "--start" => match args.next() {
+                Some(raw) => start = parse_start(&raw)?,
+                None => return Err("--start needs a COLUMN,ROW value".to_string()),
+            },
+            "--search" => search = true,
+            "--max-right" => max_right = Some(parse_limit("--max-right", args.next())? as i64),
+            "--max-down" => max_down = Some(parse_limit("--max-down", args.next())?),
+            "--render" => render = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            path => {
+                if forest_file.is_some() {
+                    return Err(format!("unexpected argument {}", path));
+                }
+                forest_file = Some(path.to_string());
+            }
+        }
     }
+
+    if slopes.is_empty() {
+        slopes = DEFAULT_SLOPES
+            .iter()
+            .map(|&(x, y)| Slope { x, y })
+            .collect();
+    }
+
+    Ok(Options {
+        forest_file: forest_file.unwrap_or_else(|| "treees.txt".to_string()),
+        slopes,
+        start,
+        search,
+        max_right,
+        max_down,
+        render,
+    })
+}
+
+#[derive(Clone)]
+struct Loc {
+    // our current index in a given row of the forest
+    curr_x: i64,
+    // our current row of the forest
+    curr_y: u32,
+    // how many trees we've found for our give slope
+    tree_count: u32,
+    // every square we've landed on, as (column, row), but only if we're going to draw it.
+    // columns aren't wrapped here, so we know which copy of the map we were on
+    visited: Vec<(i64, usize)>,
+    // the unwrapped version of curr_x, for the list above
+    path_x: i64,
+}
+
+// walks every slope through the forest,
+// returning where each one went in the order they were passed in.
+// record_path keeps every square we landed on, for render_path
+fn count_trees(forest: &Forest, slopes: &[Slope], start: &Start, record_path: bool) -> Vec<Loc> {
+    // every column repeats after this many squares
+    let width = forest.width().max(1) as i64;
     let starting_loc = Loc {
-        curr_x: 0,
-        curr_y: 0,
+        curr_x: start.x.rem_euclid(width),
+        curr_y: start.y,
         tree_count: 0,
+        visited: vec![],
+        path_x: start.x,
     };
 
-    // pairs of slopes and associated locations
-    let mut slopes: [(Slope, Loc); 5] = [
-        (Slope { x: 1, y: 1 }, starting_loc.clone()),
-        (Slope { x: 3, y: 1 }, starting_loc.clone()),
-        (Slope { x: 5, y: 1 }, starting_loc.clone()),
-        (Slope { x: 7, y: 1 }, starting_loc.clone()),
-        (Slope { x: 1, y: 2 }, starting_loc.clone()),
-    ];
+    // one location for every slope we're checking
+    let mut locs = vec![starting_loc; slopes.len()];
 
     // 🎄 --- My solution for day 3 --- 🎄
-    for (reader_index, tree_row) in tree_rows {
-        match tree_row {
-            Ok(tree_row) => {
-                let tree_row = String::from(tree_row);
-                // iterate over all slopes for a given row
-                // (means we don't have to reread the file
-                // or reallocate memory for *every* slope)
-                for (slope, loc) in slopes.iter_mut() {
-                    // if we're reading a row that matches our
-                    // current row in the forest for a given slope:
-                    if loc.curr_y == reader_index as u32 {
-                        let is_tree = check_tree_at_index(loc.curr_x as usize, &tree_row);
-                        if is_tree {
-                            loc.tree_count += 1;
-                        }
-                        // make sure to wrap to the beginning as we move to the right
-                        loc.curr_x = (loc.curr_x + slope.x) % tree_row.len() as u32;
-                        loc.curr_y += slope.y;
-                    }
+    for row in 0..forest.height() {
+        // iterate over all slopes for a given row
+        // (means we only walk the forest once,
+        // no matter how many slopes we're checking)
+        for (slope, loc) in slopes.iter().zip(locs.iter_mut()) {
+            // if we're on a row that matches our
+            // current row in the forest for a given slope:
+            if loc.curr_y as usize == row {
+                if forest.is_tree(loc.curr_x, row) {
+                    loc.tree_count += 1;
+                }
+                if record_path {
+                    loc.visited.push((loc.path_x, row));
                 }
+                loc.path_x = loc.path_x.saturating_add(slope.x);
+                // wrap around in either direction, so -1 lands on the last column
+                loc.curr_x = (loc.curr_x + slope.x.rem_euclid(width)) % width;
+                loc.curr_y = loc.curr_y.saturating_add(slope.y);
             }
-            Err(_) => println!("Something's wrong with row {}!", reader_index),
         }
     }
 
-    let mut multiplier = 1;
-    for (_, loc) in slopes.iter() {
-        multiplier *= loc.tree_count;
+    locs
+}
+
+// the most copies of the map we'll draw side by side for one path.
+// a steep slope like right 1000000000 would otherwise build rows billions of squares wide
+const MAX_RENDER_COPIES: i64 = 64;
+
+// draws the forest like the puzzle does, with O for open squares we landed on
+// and X for trees we hit. the map repeats to the right (or left) as far as the path goes,
+// up to MAX_RENDER_COPIES copies of it
+fn render_path(forest: &Forest, loc: &Loc) -> Result<String, String> {
+    let width = forest.width().max(1) as i64;
+    // figure out which copies of the map the path passes through
+    let first_copy = loc
+        .visited
+        .iter()
+        .map(|&(x, _)| x.div_euclid(width))
+        .min()
+        .unwrap_or(0)
+        .min(0);
+    let last_copy = loc
+        .visited
+        .iter()
+        .map(|&(x, _)| x.div_euclid(width))
+        .max()
+        .unwrap_or(0)
+        .max(0);
+    let copies = last_copy.saturating_sub(first_copy).saturating_add(1);
+    if copies > MAX_RENDER_COPIES {
+        return Err(format!(
+            "(this path crosses {} copies of the map, too many to draw - the limit is {})",
+            copies, MAX_RENDER_COPIES
+        ));
+    }
+
+    // a slope only ever lands once on a given row
+    let mut visited_in_row = vec![None; forest.height()];
+    for &(x, y) in loc.visited.iter() {
+        visited_in_row[y] = Some(x);
+    }
+
+    let mut map = String::new();
+    for (row, visited_x) in visited_in_row.iter().enumerate() {
+        for x in first_copy * width..(last_copy + 1) * width {
+            let square = match (forest.is_tree(x, row), *visited_x == Some(x)) {
+                (true, true) => 'X',
+                (false, true) => 'O',
+                (true, false) => '#',
+                (false, false) => '.',
+            };
+            map.push(square);
+        }
+        map.push('\n');
+    }
+    Ok(map)
+}
+
+// multiplies the tree counts together in a type wide enough for big forests.
+// returns None if even that overflows, instead of a wrapped answer
+fn tree_product(tree_counts: &[u32]) -> Option<u128> {
+    tree_counts.iter().try_fold(1u128, |product, &tree_count| {
+        product.checked_mul(tree_count as u128)
+    })
+}
+
+struct SearchResult {
+    fewest: (Slope, u32),
+    most: (Slope, u32),
+}
+
+// checks every slope from (1, 1) up to (max_right, max_down) in one walk of the forest.
+// ties go to the slope with the smallest down step, then the smallest right step
+fn search_slopes(
+    forest: &Forest,
+    max_right: i64,
+    max_down: u32,
+    start: &Start,
+) -> Option<SearchResult> {
+    // a right step of x and one of x + width hit exactly the same trees,
+    // and any down step of at least the height only ever sees the starting row,
+    // so bigger limits than these would just add copies of slopes we already check
+    // (and ties go to the smaller slope anyway)
+    let max_right = max_right.min(forest.width().max(1) as i64);
+    let max_down = max_down.min(forest.height().max(1) as u32);
+
+    let mut candidates = vec![];
+    for y in 1..=max_down {
+        for x in 1..=max_right {
+            candidates.push(Slope { x, y });
+        }
+    }
+    let tree_counts: Vec<u32> = count_trees(forest, &candidates, start, false)
+        .iter()
+        .map(|loc| loc.tree_count)
+        .collect();
+
+    let mut fewest: Option<usize> = None;
+    let mut most: Option<usize> = None;
+    for (index, &tree_count) in tree_counts.iter().enumerate() {
+        // strict comparisons keep the first slope we saw on a tie
+        if fewest.is_none_or(|fewest| tree_count < tree_counts[fewest]) {
+            fewest = Some(index);
+        }
+        if most.is_none_or(|most| tree_count > tree_counts[most]) {
+            most = Some(index);
+        }
+    }
+
+    let to_result = |index: usize| (candidates[index].clone(), tree_counts[index]);
+    match (fewest, most) {
+        (Some(fewest), Some(most)) => Some(SearchResult {
+            fewest: to_result(fewest),
+            most: to_result(most),
+        }),
+        _ => None,
     }
-    multiplier
 }
 
 fn main() {
-    let tree_rows = read_lines("treees.txt");
-    let multiplier = match tree_rows {
-        Ok(tree_rows) => count_trees(tree_rows.enumerate()),
-        Err(_) => {
-            println!("Something's wrong with this input file!");
-            0
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
         }
     };
 
+    let forest = match Forest::open(&options.forest_file) {
+        Ok(forest) => forest,
+        Err(e) => {
+            eprintln!("Something's wrong with this forest: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if options.search {
+        let max_right = options.max_right.unwrap_or(forest.width() as i64);
+        let max_down = options.max_down.unwrap_or(forest.height() as u32);
+        match search_slopes(&forest, max_right, max_down, &options.start) {
+            Some(SearchResult { fewest, most }) => {
+                println!(
+                    "Fewest trees: right {}, down {} with {} trees",
+                    fewest.0.x, fewest.0.y, fewest.1
+                );
+                println!(
+                    "Most trees: right {}, down {} with {} trees",
+                    most.0.x, most.0.y, most.1
+                );
+            }
+            None => println!("There aren't any slopes to search in this forest"),
+        }
+        return;
+    }
+
+    let locs = count_trees(&forest, &options.slopes, &options.start, options.render);
+    let tree_counts: Vec<u32> = locs.iter().map(|loc| loc.tree_count).collect();
+
+    for (slope, loc) in options.slopes.iter().zip(locs.iter()) {
+        println!(
+            "Right {}, down {}: {} trees",
+            slope.x, slope.y, loc.tree_count
+        );
+        if options.render {
+            match render_path(&forest, loc) {
+                Ok(map) => println!("{}", map),
+                Err(message) => println!("{}\n", message),
+            }
+        }
+    }
+
+    let multiplier = match tree_product(&tree_counts) {
+        Some(multiplier) => multiplier,
+        None => {
+            eprintln!("The product of these tree counts is too big to count!");
+            process::exit(1);
+        }
+    };
     println!(
         "Looks like there's a product of {} trees in this forest",
         multiplier
     )
 }
-
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
@@ -0,0 +1,111 @@
+use crate::json::JsonArrayWriter;
+use crate::passport::Passport;
+use crate::rules::to_int;
+use serde::Serialize;
+
+// a passport with its values cleaned up for other tools.
+// anything that wasn't on the passport is left empty (or null in JSON)
+#[derive(Serialize)]
+pub struct ExportedPassport {
+    pub index: usize,
+    pub line: usize,
+    pub byr: Option<String>,
+    pub iyr: Option<String>,
+    pub eyr: Option<String>,
+    // the height as written, and converted to centimeters if we could
+    pub hgt: Option<String>,
+    pub hgt_cm: Option<u32>,
+    pub hcl: Option<String>,
+    pub ecl: Option<String>,
+    // exactly as written, so leading zeros survive
+    pub pid: Option<String>,
+    pub cid: Option<String>,
+    pub valid: bool,
+}
+
+const CSV_HEADER: &str = "index,line,byr,iyr,eyr,hgt,hgt_cm,hcl,ecl,pid,cid,valid";
+
+// 1 inch = 2.54 cm, rounded to the nearest centimeter
+fn height_in_cm(hgt: &str) -> Option<u32> {
+    if let Some(cm) = hgt.strip_suffix("cm") {
+        return to_int(cm);
+    }
+    let inches = to_int(hgt.strip_suffix("in")?)?;
+    Some(((inches as f64) * 2.54).round() as u32)
+}
+
+pub fn normalize(index: usize, passport: &Passport, valid: bool) -> ExportedPassport {
+    ExportedPassport {
+        index,
+        line: passport.line,
+        byr: passport.byr.clone(),
+        iyr: passport.iyr.clone(),
+        eyr: passport.eyr.clone(),
+        hgt: passport.hgt.clone(),
+        hgt_cm: passport.hgt.as_deref().and_then(height_in_cm),
+        hcl: passport.hcl.as_ref().map(|hcl| hcl.to_lowercase()),
+        ecl: passport.ecl.clone(),
+        pid: passport.pid.clone(),
+        cid: passport.cid.clone(),
+        valid,
+    }
+}
+
+// quote a CSV value if it has anything that would break the row apart
+fn csv_value(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(passport: &ExportedPassport) -> String {
+    let optional = |value: &Option<String>| value.as_deref().map(csv_value).unwrap_or_default();
+    [
+        passport.index.to_string(),
+        passport.line.to_string(),
+        optional(&passport.byr),
+        optional(&passport.iyr),
+        optional(&passport.eyr),
+        optional(&passport.hgt),
+        passport
+            .hgt_cm
+            .map(|hgt_cm| hgt_cm.to_string())
+            .unwrap_or_default(),
+        optional(&passport.hcl),
+        optional(&passport.ecl),
+        optional(&passport.pid),
+        optional(&passport.cid),
+        passport.valid.to_string(),
+    ]
+    .join(",")
+}
+
+// writes passports out one at a time, as we read them
+pub enum Exporter {
+    Csv,
+    Json(JsonArrayWriter),
+}
+
+impl Exporter {
+    pub fn start(&self) {
+        match self {
+            Exporter::Csv => println!("{}", CSV_HEADER),
+            Exporter::Json(writer) => writer.start(),
+        }
+    }
+
+    pub fn export(&mut self, passport: &ExportedPassport) {
+        match self {
+            Exporter::Csv => println!("{}", csv_row(passport)),
+            Exporter::Json(writer) => writer.write(passport),
+        }
+    }
+
+    pub fn finish(&self) {
+        if let Exporter::Json(writer) = self {
+            writer.finish();
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use serde::Serialize;
+
+// writes a JSON array out one element at a time, as we go,
+// so we never have to hold the whole batch in memory
+pub struct JsonArrayWriter {
+    first: bool,
+    // each element over several lines, or on one indented line
+    pretty: bool,
+}
+
+impl JsonArrayWriter {
+    pub fn new(pretty: bool) -> JsonArrayWriter {
+        JsonArrayWriter {
+            first: true,
+            pretty,
+        }
+    }
+
+    pub fn start(&self) {
+        println!("[");
+    }
+
+    pub fn write<T: Serialize>(&mut self, value: &T) {
+        if !self.first {
+            println!(",");
+        }
+        self.first = false;
+        // everything we write is a plain struct of strings and numbers, which can't fail
+        if self.pretty {
+            print!("{}", serde_json::to_string_pretty(value).unwrap());
+        } else {
+            print!("  {}", serde_json::to_string(value).unwrap());
+        }
+    }
+
+    pub fn finish(&self) {
+        println!("\n]");
+    }
+}
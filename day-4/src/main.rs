@@ -1,142 +1,273 @@
-use std::fs::read_to_string;
-use std::collections::HashSet;
-use regex::Regex;
-
-//  key
-//      on: colon -> value
-//  value
-//      on: space, ONE \n -> key
-//      on: TWO \n -> parse_passport
-//  parse_passport
-//      action: check keys, count up if valid
-//          onDone -> key
-
-const VALID_EYE_COLORS: &[&str] = &[
-    "amb",
-    "blu",
-    "brn",
-    "gry",
-    "grn",
-    "hzl",
-    "oth"
-];
-
-fn to_int(value: &str) -> u32 {
-    match value.parse::<u32>() {
-        Ok(i) => i,
-        Err(_e) => 0
-    }
+mod export;
+mod json;
+mod passport;
+mod report;
+mod rules;
+mod stats;
+
+use export::Exporter;
+use json::JsonArrayWriter;
+use passport::{ParseError, Passport, PassportReader};
+use report::{KeyPolicies, KeyPolicy, PassportReport, ReportPrinter, Tally};
+use rules::Rules;
+use stats::Stats;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+
+const USAGE: &str = "usage: day-4 [PASSPORT_FILE|-] [--rules RULES_JSON] [--report table|json] \
+                     [--mode lenient|strict|both] [--duplicate-keys reject|warn|ignore] \
+                     [--unknown-keys reject|warn|ignore] [--export csv|json] [--stats]";
+
+enum ReportFormat {
+    Table,
+    Json,
 }
 
-fn is_valid_passport_value(key: &str, value: &str) -> bool {
-    match key {
-        "byr" => to_int(value) >= 1920 && to_int(value) <= 2002,
-        "iyr" => to_int(value) >= 2010 && to_int(value) <= 2020,
-        "eyr" => to_int(value) >= 2020 && to_int(value) <= 2030,
-        "hgt" => {
-            let split = value.len() - 2;
-            match &value[split..] {
-                "cm" => {
-                    let measurement = to_int(&value[..split]);
-                    measurement >= 150 && measurement <= 193
-                },
-                "in" => {
-                    let measurement = to_int(&value[..split]);
-                    measurement >= 59 && measurement <= 76
-                },
-                _ => false
-            }
-        },
-        "hcl" => Regex::new(r"^#[0-9a-fA-F]{6}$").unwrap().is_match(value),
-        "ecl" => VALID_EYE_COLORS.contains(&value),
-        "pid" => value.len() == 9,
-        _ => false
-    }
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+enum Mode {
+    // part 1: every required key is there
+    Lenient,
+    // part 2: every required key is there and passes its rule
+    Strict,
+    Both,
+}
+
+struct Options {
+    // - reads from stdin
+    passport_file: String,
+    // falls back to the puzzle's rules (see rules.json)
+    rules_file: Option<String>,
+    // print why every passport passed or failed, instead of just the count
+    report: Option<ReportFormat>,
+    mode: Mode,
+    policies: KeyPolicies,
+    // write out the passports themselves, instead of a report
+    export: Option<ExportFormat>,
+    // print field statistics for the whole batch after the summary
+    stats: bool,
 }
 
-fn read_passports(raw_passports: String) -> u32 {
-    enum State {
-        ReadKeys,
-        ReadValues,
+fn parse_policy(flag: &str, raw: Option<String>) -> Result<KeyPolicy, String> {
+    match raw.as_deref() {
+        Some("reject") => Ok(KeyPolicy::Reject),
+        Some("warn") => Ok(KeyPolicy::Warn),
+        Some("ignore") => Ok(KeyPolicy::Ignore),
+        _ => Err(format!("{} needs to be reject, warn or ignore", flag)),
     }
+}
 
-    let required_keys: HashSet<String> = [
-        "byr".to_string(),
-        "iyr".to_string(),
-        "eyr".to_string(),
-        "hgt".to_string(),
-        "hcl".to_string(),
-        "ecl".to_string(),
-        "pid".to_string()
-    ].iter().cloned().collect();
-
-    let mut num_valid_passports = 0;
-    let mut state = State::ReadKeys;
-    let mut keys_unaccounted_for: HashSet<String> = required_keys.clone();
-    let mut curr_key = String::from("");
-    let mut curr_value = String::from("");
-
-    for c in raw_passports.chars() {
-        state = match state {
-            State::ReadKeys => {
-                match c {
-                    // if we find a new-line, that (hopefully) means
-                    // we found the first new-line in State::ReadValues
-                    // and we bounced over here. 2 new lines = end of passport
-                    '\n' => {
-                        if keys_unaccounted_for.is_empty() {
-                            num_valid_passports += 1;
-                        }
-                        // reset keys unaccounted for after validating the passport
-                        keys_unaccounted_for = required_keys.clone();
-                        State::ReadKeys
-                    },
-                    // on a colon, jump to reading the value
-                    ':' => State::ReadValues,
-                    c => {
-                        // read the character into our running key
-                        curr_key.push_str(&c.to_string());
-                        State::ReadKeys
-                    }
-                }
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut passport_file = None;
+    let mut rules_file = None;
+    let mut report = None;
+    let mut mode = Mode::Both;
+    let mut export = None;
+    let mut stats = false;
+    let mut policies = KeyPolicies {
+        duplicate: KeyPolicy::Ignore,
+        unknown: KeyPolicy::Ignore,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" => match args.next() {
+                Some(path) => rules_file = Some(path),
+                None => return Err("--rules needs a path".to_string()),
             },
-            State::ReadValues => {
-                match c {
-                    ' ' | '\n' => {
-                        // if we're done reading the value,
-                        // we're ready to validate the key / value pair
-                        if is_valid_passport_value(&curr_key, &curr_value) {
-                            keys_unaccounted_for.remove(&curr_key);
-                        }
-                        curr_key.clear();
-                        curr_value.clear();
-                        State::ReadKeys
-                    },
-                    c => {
-                        // read the character into our running value
-                        curr_value.push_str(&c.to_string());
-                        State::ReadValues
-                    }
-                }
+            "--report" => match args.next().as_deref() {
+                Some("table") => report = Some(ReportFormat::Table),
+                Some("json") => report = Some(ReportFormat::Json),
+                _ => return Err("--report needs to be table or json".to_string()),
+            },
+            "--mode" => match args.next().as_deref() {
+                Some("lenient") => mode = Mode::Lenient,
+                Some("strict") => mode = Mode::Strict,
+                Some("both") => mode = Mode::Both,
+                _ => return Err("--mode needs to be lenient, strict or both".to_string()),
             },
+            "--duplicate-keys" => policies.duplicate = parse_policy(&arg, args.next())?,
+            "--unknown-keys" => policies.unknown = parse_policy(&arg, args.next())?,
+            "--export" => match args.next().as_deref() {
+                Some("csv") => export = Some(ExportFormat::Csv),
+                Some("json") => export = Some(ExportFormat::Json),
+                _ => return Err("--export needs to be csv or json".to_string()),
+            },
+            "--stats" => stats = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            path => {
+                if passport_file.is_some() {
+                    return Err(format!("unexpected argument {}", path));
+                }
+                passport_file = Some(path.to_string());
+            }
         }
     }
-    num_valid_passports
+
+    if report.is_some() && export.is_some() {
+        return Err("pick either --report or --export, not both".to_string());
+    }
+
+    if stats && (export.is_some() || matches!(report, Some(ReportFormat::Json))) {
+        return Err("--stats can't be combined with --export or --report json".to_string());
+    }
+
+    Ok(Options {
+        passport_file: passport_file.unwrap_or_else(|| "passports.txt".to_string()),
+        rules_file,
+        report,
+        mode,
+        policies,
+        export,
+        stats,
+    })
+}
+
+fn warn_about_keys(report: &PassportReport, policies: &KeyPolicies) {
+    if policies.duplicate == KeyPolicy::Warn && !report.duplicate_keys.is_empty() {
+        eprintln!(
+            "warning: passport {} (line {}) repeats {}",
+            report.index,
+            report.line,
+            report.duplicate_keys.join(", ")
+        );
+    }
+    if policies.unknown == KeyPolicy::Warn && !report.unknown_keys.is_empty() {
+        eprintln!(
+            "warning: passport {} (line {}) has unknown keys {}",
+            report.index,
+            report.line,
+            report.unknown_keys.join(", ")
+        );
+    }
+}
+
+// validates passports as they stream in, handing each one to on_report
+fn read_passports<R: BufRead>(
+    reader: R,
+    rules: &Rules,
+    policies: &KeyPolicies,
+    mut on_report: impl FnMut(&Passport, &PassportReport),
+) -> Result<(), ParseError> {
+    for (index, passport) in PassportReader::new(reader).enumerate() {
+        let passport = passport?;
+        let report = report::report_passport(index + 1, &passport, rules, policies);
+        warn_about_keys(&report, policies);
+        on_report(&passport, &report);
+    }
+    Ok(())
 }
 
 fn main() {
-    let passports = read_to_string("passports.txt");
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+
+    let rules = match &options.rules_file {
+        Some(path) => Rules::load(path),
+        None => Ok(Rules::puzzle_rules()),
+    };
+    let rules = match rules {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
 
-    let num_valid_passports = match passports {
-        Ok(passports) => read_passports(passports),
-        Err(_) => {
-            println!("Something's wrong with this input file!");
-            0
+    let reader: Box<dyn BufRead> = if options.passport_file == "-" {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        match File::open(&options.passport_file) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) => {
+                // on stderr, so a script reading --report json or --export doesn't get it
+                eprintln!("Something's wrong with this input file: {}", e);
+                process::exit(1);
+            }
         }
     };
 
-    println!(
-        "Looks like there's {} valid passports here",
-        num_valid_passports
-    )
-}
\ No newline at end of file
+    let mut printer = match options.report {
+        Some(ReportFormat::Table) => Some(ReportPrinter::Table),
+        Some(ReportFormat::Json) => Some(ReportPrinter::Json(JsonArrayWriter::new(true))),
+        None => None,
+    };
+    if let Some(printer) = &printer {
+        printer.start();
+    }
+    let mut exporter = match options.export {
+        Some(ExportFormat::Csv) => Some(Exporter::Csv),
+        Some(ExportFormat::Json) => Some(Exporter::Json(JsonArrayWriter::new(false))),
+        None => None,
+    };
+    if let Some(exporter) = &exporter {
+        exporter.start();
+    }
+
+    let mut tally = Tally::default();
+    let mut stats = Stats::default();
+    let result = read_passports(reader, &rules, &options.policies, |passport, report| {
+        tally.add(report);
+        if options.stats {
+            stats.add(passport, report, &rules);
+        }
+        if let Some(printer) = &mut printer {
+            printer.print(report);
+        }
+        if let Some(exporter) = &mut exporter {
+            exporter.export(&export::normalize(report.index, passport, report.valid));
+        }
+    });
+    if let Some(printer) = &printer {
+        printer.finish();
+    }
+    if let Some(exporter) = &exporter {
+        exporter.finish();
+    }
+    if let Err(e) = result {
+        eprintln!("Something's wrong with this passport: {}", e);
+        process::exit(1);
+    }
+    if matches!(options.report, Some(ReportFormat::Json)) || options.export.is_some() {
+        // keep stdout as valid JSON / CSV
+        return;
+    }
+
+    match options.mode {
+        Mode::Lenient => println!(
+            "Looks like there's {} passports with every field here",
+            tally.complete
+        ),
+        Mode::Strict => println!("Looks like there's {} valid passports here", tally.valid),
+        Mode::Both => println!(
+            "Out of {} passports, there's \n\
+            - {} with every field (part 1) \n\
+            - {} with every field valid (part 2) \n\
+            - {} missing a field \n\
+            - {} with every field, but some invalid \n\
+            - {} rejected for duplicate or unknown keys",
+            tally.total,
+            tally.complete,
+            tally.valid,
+            tally.missing_a_field,
+            tally.with_invalid_fields,
+            tally.rejected
+        ),
+    }
+
+    if options.stats {
+        println!();
+        stats.print(&rules);
+    }
+}
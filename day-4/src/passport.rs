@@ -0,0 +1,209 @@
+use std::fmt;
+use std::io::BufRead;
+
+// a single passport, as written in the batch file.
+// values are kept exactly as they were written,
+// so the rules can explain what's wrong with them
+#[derive(Default)]
+pub struct Passport {
+    // the line the passport starts on, starting from 1
+    pub line: usize,
+    pub byr: Option<String>,
+    pub iyr: Option<String>,
+    pub eyr: Option<String>,
+    pub hgt: Option<String>,
+    pub hcl: Option<String>,
+    pub ecl: Option<String>,
+    pub pid: Option<String>,
+    pub cid: Option<String>,
+    // any keys the puzzle doesn't know about, in the order we found them
+    pub extras: Vec<(String, String)>,
+    // keys that showed up more than once (we keep the first value)
+    pub duplicates: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Passport {
+    fn known_field(&mut self, key: &str) -> Option<&mut Option<String>> {
+        match key {
+            "byr" => Some(&mut self.byr),
+            "iyr" => Some(&mut self.iyr),
+            "eyr" => Some(&mut self.eyr),
+            "hgt" => Some(&mut self.hgt),
+            "hcl" => Some(&mut self.hcl),
+            "ecl" => Some(&mut self.ecl),
+            "pid" => Some(&mut self.pid),
+            "cid" => Some(&mut self.cid),
+            _ => None,
+        }
+    }
+
+    fn insert(&mut self, key: &str, value: &str) {
+        match self.known_field(key) {
+            // the first value for a key wins
+            Some(field) if field.is_none() => *field = Some(value.to_string()),
+            Some(_) => self.duplicates.push(key.to_string()),
+            None => {
+                if self.extras.iter().any(|(extra_key, _)| extra_key == key) {
+                    self.duplicates.push(key.to_string());
+                } else {
+                    self.extras.push((key.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields()
+            .into_iter()
+            .find(|&(field_key, _)| field_key == key)
+            .map(|(_, value)| value)
+    }
+
+    // every key / value pair on the passport, known keys first
+    pub fn fields(&self) -> Vec<(&str, &str)> {
+        let known = [
+            ("byr", &self.byr),
+            ("iyr", &self.iyr),
+            ("eyr", &self.eyr),
+            ("hgt", &self.hgt),
+            ("hcl", &self.hcl),
+            ("ecl", &self.ecl),
+            ("pid", &self.pid),
+            ("cid", &self.cid),
+        ];
+        let mut fields: Vec<(&str, &str)> = known
+            .iter()
+            .filter_map(|&(key, value)| value.as_deref().map(|value| (key, value)))
+            .collect();
+        fields.extend(
+            self.extras
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+        fields
+    }
+}
+
+// reads one passport at a time from any buffered reader (a file, stdin...),
+// so memory stays the same no matter how big the batch is.
+//
+// passports are runs of key:value pairs separated by spaces or newlines,
+// with a blank line between passports.
+// handles \r\n line endings, trailing whitespace, and a missing blank line at the end
+pub struct PassportReader<R: BufRead> {
+    reader: R,
+    // reused for every line we read
+    raw_line: String,
+    // the line number of whatever's in raw_line, starting from 1
+    line: usize,
+    done: bool,
+}
+
+impl<R: BufRead> PassportReader<R> {
+    pub fn new(reader: R) -> PassportReader<R> {
+        PassportReader {
+            reader,
+            raw_line: String::new(),
+            line: 0,
+            done: false,
+        }
+    }
+
+    fn read_passport(&mut self) -> Result<Option<Passport>, ParseError> {
+        let mut curr_passport: Option<Passport> = None;
+
+        loop {
+            self.raw_line.clear();
+            self.line += 1;
+            let line = self.line;
+            let bytes_read = self
+                .reader
+                .read_line(&mut self.raw_line)
+                .map_err(|e| ParseError {
+                    line,
+                    column: 1,
+                    message: format!("couldn't read this line: {}", e),
+                })?;
+            // the file might not end with a blank line
+            if bytes_read == 0 {
+                self.done = true;
+                return Ok(curr_passport);
+            }
+
+            let raw_line = self.raw_line.trim_end_matches(&['\n', '\r'][..]);
+            if raw_line.trim().is_empty() {
+                // a blank line means the passport we were reading is done
+                match curr_passport {
+                    Some(passport) => return Ok(Some(passport)),
+                    None => continue,
+                }
+            }
+
+            let passport = curr_passport.get_or_insert_with(|| Passport {
+                line,
+                ..Passport::default()
+            });
+            // split on every whitespace character so we can count columns as we go
+            let mut column = 1;
+            for field in raw_line.split(char::is_whitespace) {
+                if !field.is_empty() {
+                    match field.split_once(':') {
+                        Some((key, value)) if !key.is_empty() => passport.insert(key, value),
+                        Some(_) => {
+                            return Err(ParseError {
+                                line,
+                                column,
+                                message: format!("\"{}\" is missing a key before the colon", field),
+                            })
+                        }
+                        None => {
+                            return Err(ParseError {
+                                line,
+                                column,
+                                message: format!("\"{}\" should look like key:value", field),
+                            })
+                        }
+                    }
+                }
+                column += field.chars().count() + 1;
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PassportReader<R> {
+    type Item = Result<Passport, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_passport() {
+            Ok(passport) => passport.map(Ok),
+            Err(e) => {
+                // stop after the first error, since we've lost our place in the file
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::json::JsonArrayWriter;
+use crate::passport::Passport;
+use crate::rules::Rules;
+use serde::Serialize;
+
+// what to do with a passport that has duplicate or unknown keys
+#[derive(Clone, Copy, PartialEq)]
+pub enum KeyPolicy {
+    // the passport fails, whatever else is on it
+    Reject,
+    // print a warning, but judge the passport like normal
+    Warn,
+    Ignore,
+}
+
+pub struct KeyPolicies {
+    pub duplicate: KeyPolicy,
+    pub unknown: KeyPolicy,
+}
+
+// one operation in a JSON Patch (RFC 6902)
+#[derive(Serialize)]
+pub struct PatchOp {
+    pub op: &'static str,
+    pub path: String,
+    pub value: String,
+}
+
+// a suggested fix for a passport that only fails on one field, as a JSON Patch.
+// the test op makes the patch fail if the value isn't what we saw,
+// before the replace swaps it out
+pub type Repair = Vec<PatchOp>;
+
+#[derive(Serialize)]
+pub struct InvalidField {
+    pub key: String,
+    pub value: String,
+    pub reason: String,
+}
+
+// everything we found out about a single passport
+#[derive(Serialize)]
+pub struct PassportReport {
+    // which passport this is in the file, starting from 1
+    pub index: usize,
+    // the line the passport starts on, starting from 1
+    pub line: usize,
+    // every required key is there (part 1), whatever the key policies say
+    pub complete: bool,
+    // ...and every value passes its rule (part 2), and the key policies didn't reject it
+    pub valid: bool,
+    pub missing: Vec<String>,
+    pub invalid: Vec<InvalidField>,
+    // failed because of the duplicate or unknown key policy
+    pub rejected: bool,
+    // only filled in when the policy for them isn't Ignore
+    pub duplicate_keys: Vec<String>,
+    pub unknown_keys: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repair: Option<Repair>,
+}
+
+pub fn report_passport(
+    index: usize,
+    passport: &Passport,
+    rules: &Rules,
+    policies: &KeyPolicies,
+) -> PassportReport {
+    let missing: Vec<String> = rules
+        .required_keys()
+        .filter(|&required_key| passport.get(required_key).is_none())
+        .map(String::from)
+        .collect();
+
+    let invalid: Vec<InvalidField> = passport
+        .fields()
+        .into_iter()
+        .filter_map(|(key, value)| match rules.check(key, value) {
+            Some(Err(reason)) => Some(InvalidField {
+                key: key.to_string(),
+                value: value.to_string(),
+                reason,
+            }),
+            // fine, or a key we don't have a rule for
+            _ => None,
+        })
+        .collect();
+
+    let duplicate_keys = match policies.duplicate {
+        KeyPolicy::Ignore => vec![],
+        _ => passport.duplicates.clone(),
+    };
+    // keys the passport doesn't know about, that the rules don't mention either
+    let unknown_keys: Vec<String> = match policies.unknown {
+        KeyPolicy::Ignore => vec![],
+        _ => passport
+            .extras
+            .iter()
+            .filter(|(key, _)| !rules.has_rule(key))
+            .map(|(key, _)| key.clone())
+            .collect(),
+    };
+    let rejected = (policies.duplicate == KeyPolicy::Reject && !duplicate_keys.is_empty())
+        || (policies.unknown == KeyPolicy::Reject && !unknown_keys.is_empty());
+
+    // only worth suggesting when fixing one field would make the whole passport valid
+    let repair = match invalid.as_slice() {
+        [field] if missing.is_empty() && !rejected => {
+            rules.repair(&field.key, &field.value).map(|value| {
+                vec![
+                    PatchOp {
+                        op: "test",
+                        path: format!("/{}", field.key),
+                        value: field.value.clone(),
+                    },
+                    PatchOp {
+                        op: "replace",
+                        path: format!("/{}", field.key),
+                        value,
+                    },
+                ]
+            })
+        }
+        _ => None,
+    };
+
+    PassportReport {
+        index,
+        line: passport.line,
+        complete: missing.is_empty(),
+        valid: missing.is_empty() && invalid.is_empty() && !rejected,
+        missing,
+        invalid,
+        rejected,
+        duplicate_keys,
+        unknown_keys,
+        repair,
+    }
+}
+
+// running totals across a batch, so we don't have to hold on to every report.
+// valid, missing_a_field, with_invalid_fields and rejected never overlap,
+// so they always add up to total
+#[derive(Default)]
+pub struct Tally {
+    pub total: usize,
+    pub complete: usize,
+    pub valid: usize,
+    pub missing_a_field: usize,
+    pub with_invalid_fields: usize,
+    pub rejected: usize,
+}
+
+impl Tally {
+    pub fn add(&mut self, report: &PassportReport) {
+        self.total += 1;
+        self.complete += report.complete as usize;
+        self.valid += report.valid as usize;
+        self.missing_a_field += (!report.complete && !report.rejected) as usize;
+        self.with_invalid_fields += (report.complete && !report.valid && !report.rejected) as usize;
+        self.rejected += report.rejected as usize;
+    }
+}
+
+// prints reports one at a time, as we validate them
+pub enum ReportPrinter {
+    Table,
+    Json(JsonArrayWriter),
+}
+
+impl ReportPrinter {
+    pub fn start(&self) {
+        match self {
+            ReportPrinter::Table => {
+                println!("{:>6}  {:>6}  {:<10}  problems", "#", "line", "status")
+            }
+            ReportPrinter::Json(writer) => writer.start(),
+        }
+    }
+
+    pub fn print(&mut self, report: &PassportReport) {
+        match self {
+            ReportPrinter::Table => print_table_row(report),
+            ReportPrinter::Json(writer) => writer.write(report),
+        }
+    }
+
+    pub fn finish(&self) {
+        if let ReportPrinter::Json(writer) = self {
+            writer.finish();
+        }
+    }
+}
+
+fn print_table_row(report: &PassportReport) {
+    let mut problems: Vec<String> = report
+        .missing
+        .iter()
+        .map(|key| format!("{}: missing", key))
+        .collect();
+    for field in report.invalid.iter() {
+        problems.push(format!("{}: {}", field.key, field.reason));
+    }
+    for key in report.duplicate_keys.iter() {
+        problems.push(format!("{}: duplicate key", key));
+    }
+    for key in report.unknown_keys.iter() {
+        problems.push(format!("{}: unknown key", key));
+    }
+    let status = if report.rejected {
+        "rejected"
+    } else if report.valid {
+        "valid"
+    } else if report.complete {
+        "invalid"
+    } else {
+        "incomplete"
+    };
+    println!(
+        "{:>6}  {:>6}  {:<10}  {}",
+        report.index,
+        report.line,
+        status,
+        problems.join("; ")
+    );
+    if let Some(repair) = &report.repair {
+        println!(
+            "{:>6}  {:>6}  {:<10}  {}",
+            "",
+            "",
+            "patch",
+            serde_json::to_string(repair).unwrap()
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read_to_string;
+use std::ops::RangeInclusive;
+
+// the puzzle's rules, used when no rules file is passed in
+const DEFAULT_RULES: &str = include_str!("../rules.json");
+
+// --- what the rules file looks like ---
+
+#[derive(Deserialize)]
+struct RulesFile {
+    fields: Vec<FieldRule>,
+}
+
+#[derive(Deserialize)]
+struct FieldRule {
+    key: String,
+    rule: Rule,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Rule {
+    // a number between min and max (inclusive)
+    Range { min: u32, max: u32 },
+    // a number followed by one of these units, each with their own range
+    Units(Vec<UnitRange>),
+    // anything the pattern matches, from start to end
+    Regex(String),
+    // one of a fixed list of values
+    Enum(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct UnitRange {
+    unit: String,
+    min: u32,
+    max: u32,
+}
+
+// --- the compiled version we validate with ---
+
+enum Check {
+    Range(RangeInclusive<u32>),
+    Units(Vec<(String, RangeInclusive<u32>)>),
+    // the compiled regex, and the pattern as written in the rules file
+    Regex(Regex, String),
+    Enum(Vec<String>),
+}
+
+struct FieldCheck {
+    key: String,
+    check: Check,
+}
+
+pub struct Rules {
+    fields: Vec<FieldCheck>,
+}
+
+#[derive(Debug)]
+pub enum RulesError {
+    Unreadable {
+        path: String,
+        source: std::io::Error,
+    },
+    Malformed(serde_json::Error),
+    BadRegex {
+        key: String,
+        source: regex::Error,
+    },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulesError::Unreadable { path, source } => {
+                write!(f, "couldn't read rules file {}: {}", path, source)
+            }
+            RulesError::Malformed(source) => write!(f, "rules file is malformed: {}", source),
+            RulesError::BadRegex { key, source } => {
+                write!(f, "the pattern for {} doesn't compile: {}", key, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+// only plain digits count as a number here.
+// str::parse would also take a leading +, which isn't something a passport should have
+pub fn to_int(value: &str) -> Option<u32> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse::<u32>().ok()
+}
+
+// true if one character added, removed or swapped out turns a into b
+fn one_edit_apart(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (shorter, longer) = if a.len() <= b.len() {
+        (&a, &b)
+    } else {
+        (&b, &a)
+    };
+    if longer.len() - shorter.len() > 1 {
+        return false;
+    }
+    // skip the matching start and end, and see what's left in the middle
+    let prefix = shorter
+        .iter()
+        .zip(longer.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = shorter[prefix..]
+        .iter()
+        .rev()
+        .zip(longer[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    longer.len() - prefix - suffix <= 1 && shorter.len() - prefix - suffix <= 1
+}
+
+fn describe_range(range: &RangeInclusive<u32>) -> String {
+    format!("{}..={}", range.start(), range.end())
+}
+
+impl Check {
+    // explains what's wrong with the value, if anything
+    fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Check::Range(range) => match to_int(value) {
+                Some(i) if range.contains(&i) => Ok(()),
+                Some(_) => Err(format!("{} outside {}", value, describe_range(range))),
+                None => Err(format!("{} isn't a number", value)),
+            },
+            Check::Units(units) => {
+                // find the unit the value ends in, then check the number in front of it
+                let unit_range = units.iter().find_map(|(unit, range)| {
+                    value
+                        .strip_suffix(unit.as_str())
+                        .map(|measurement| (measurement, range))
+                });
+                match unit_range {
+                    Some((measurement, range)) => match to_int(measurement) {
+                        Some(i) if range.contains(&i) => Ok(()),
+                        Some(_) => Err(format!("{} outside {}", value, describe_range(range))),
+                        None => Err(format!("{} isn't a number", measurement)),
+                    },
+                    None => {
+                        let unit_names: Vec<&str> =
+                            units.iter().map(|(unit, _)| unit.as_str()).collect();
+                        Err(format!(
+                            "{} doesn't end in {}",
+                            value,
+                            unit_names.join(" or ")
+                        ))
+                    }
+                }
+            }
+            Check::Regex(regex, _) if regex.is_match(value) => Ok(()),
+            Check::Regex(_, pattern) => Err(format!("{} doesn't match {}", value, pattern)),
+            Check::Enum(values) if values.iter().any(|allowed| allowed == value) => Ok(()),
+            Check::Enum(values) => Err(format!("{} isn't one of {}", value, values.join(", "))),
+        }
+    }
+}
+
+impl Check {
+    // the smallest change we can think of that makes an invalid value pass
+    fn repair(&self, value: &str) -> Option<String> {
+        let candidates: Vec<String> = match self {
+            // a number's either in range or it isn't, there's no small fix to make
+            Check::Range(_) => vec![],
+            // a bare number, where only one unit makes it valid
+            Check::Units(units) => match to_int(value) {
+                Some(measurement) => {
+                    let fitting: Vec<String> = units
+                        .iter()
+                        .filter(|(_, range)| range.contains(&measurement))
+                        .map(|(unit, _)| format!("{}{}", value, unit))
+                        .collect();
+                    if fitting.len() == 1 {
+                        fitting
+                    } else {
+                        vec![]
+                    }
+                }
+                None => vec![],
+            },
+            // a couple of common slip-ups: a missing #, a missing leading 0, uppercase
+            Check::Regex(..) => vec![
+                format!("#{}", value),
+                format!("0{}", value),
+                value.to_lowercase(),
+            ],
+            // a typo, as long as there's only one value it could have meant
+            Check::Enum(values) => {
+                let close: Vec<String> = values
+                    .iter()
+                    .filter(|allowed| one_edit_apart(allowed, value))
+                    .cloned()
+                    .collect();
+                if close.len() == 1 {
+                    close
+                } else {
+                    vec![]
+                }
+            }
+        };
+        candidates
+            .into_iter()
+            .find(|candidate| candidate != value && self.check(candidate).is_ok())
+    }
+}
+
+impl Rules {
+    pub fn load(path: &str) -> Result<Rules, RulesError> {
+        let raw = read_to_string(path).map_err(|source| RulesError::Unreadable {
+            path: path.to_string(),
+            source,
+        })?;
+        Rules::from_json(&raw)
+    }
+
+    pub fn puzzle_rules() -> Rules {
+        Rules::from_json(DEFAULT_RULES).expect("the bundled rules.json should always compile")
+    }
+
+    // compiles every rule up front, so we don't rebuild regexes per passport
+    pub fn from_json(raw: &str) -> Result<Rules, RulesError> {
+        let rules_file: RulesFile = serde_json::from_str(raw).map_err(RulesError::Malformed)?;
+        let mut fields = vec![];
+        // fields that share a pattern share the compiled regex too
+        let mut regex_cache: HashMap<String, Regex> = HashMap::new();
+        for FieldRule { key, rule } in rules_file.fields {
+            let check = match rule {
+                Rule::Range { min, max } => Check::Range(min..=max),
+                Rule::Units(units) => Check::Units(
+                    units
+                        .into_iter()
+                        .map(|UnitRange { unit, min, max }| (unit, min..=max))
+                        .collect(),
+                ),
+                Rule::Regex(pattern) => match regex_cache.get(&pattern) {
+                    Some(regex) => Check::Regex(regex.clone(), pattern),
+                    None => {
+                        // always match the whole value, so a pattern like [0-9]{9}
+                        // can't be satisfied by a value that just contains 9 digits
+                        let regex = match Regex::new(&format!("^(?:{})$", pattern)) {
+                            Ok(regex) => regex,
+                            Err(source) => return Err(RulesError::BadRegex { key, source }),
+                        };
+                        regex_cache.insert(pattern.clone(), regex.clone());
+                        Check::Regex(regex, pattern)
+                    }
+                },
+                Rule::Enum(values) => Check::Enum(values),
+            };
+            fields.push(FieldCheck { key, check });
+        }
+        Ok(Rules { fields })
+    }
+
+    // every field with a rule has to be on the passport
+    pub fn required_keys(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|field| field.key.as_str())
+    }
+
+    pub fn has_rule(&self, key: &str) -> bool {
+        self.fields.iter().any(|field| field.key == key)
+    }
+
+    pub fn repair(&self, key: &str, value: &str) -> Option<String> {
+        self.fields
+            .iter()
+            .find(|field| field.key == key)
+            .and_then(|field| field.check.repair(value))
+    }
+
+    // None when there's no rule for this key (like cid)
+    pub fn check(&self, key: &str, value: &str) -> Option<Result<(), String>> {
+        self.fields
+            .iter()
+            .find(|field| field.key == key)
+            .map(|field| field.check.check(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const PUZZLE_KEYS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+
+    proptest! {
+        // passport values come from untrusted files, so no value should ever panic a rule
+        #[test]
+        fn puzzle_rules_never_panic(value in any::<String>()) {
+            let rules = Rules::puzzle_rules();
+            for key in PUZZLE_KEYS.iter() {
+                prop_assert!(rules.check(key, &value).is_some());
+                let _ = rules.repair(key, &value);
+            }
+        }
+
+        // a rules file can have units and values the puzzle doesn't,
+        // like multi-byte units and numbers too big for a u32
+        #[test]
+        fn custom_rules_never_panic(
+            value in prop_oneof![any::<String>(), "[+0-9]{0,12}(µm|ft|in|é)?", "[a-zé]{0,4}"]
+        ) {
+            let rules = Rules::from_json(
+                r#"{ "fields": [
+                    { "key": "len", "rule": { "units": [
+                        { "unit": "µm", "min": 0, "max": 4294967295 },
+                        { "unit": "ft", "min": 3, "max": 9 }
+                    ] } },
+                    { "key": "id", "rule": { "regex": "[a-z]+|[0-9]{3}" } },
+                    { "key": "tag", "rule": { "enum": ["é", "ab", "abé"] } },
+                    { "key": "age", "rule": { "range": { "min": 0, "max": 4294967295 } } }
+                ] }"#,
+            )
+            .unwrap();
+            for key in ["len", "id", "tag", "age"].iter() {
+                prop_assert!(rules.check(key, &value).is_some());
+                if let Some(repaired) = rules.repair(key, &value) {
+                    // a repair should always pass the rule it was made for
+                    prop_assert_eq!(rules.check(key, &repaired), Some(Ok(())));
+                }
+            }
+        }
+
+        // only plain digits are numbers, whatever's in front of them
+        #[test]
+        fn to_int_only_takes_digits(value in "[+-]?[0-9]{1,9}") {
+            prop_assert_eq!(to_int(&value).is_some(), value.bytes().all(|b| b.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn short_and_multi_byte_heights() {
+        let rules = Rules::puzzle_rules();
+        // shorter than either unit
+        assert!(rules.check("hgt", "1").unwrap().is_err());
+        assert!(rules.check("hgt", "m").unwrap().is_err());
+        // é is two bytes, so slicing off the last two bytes would split it
+        assert!(rules.check("hgt", "1é").unwrap().is_err());
+        assert!(rules.check("hgt", "éin").unwrap().is_err());
+        assert!(rules.check("hgt", "150cm").unwrap().is_ok());
+    }
+
+    #[test]
+    fn leading_plus_is_not_a_number() {
+        let rules = Rules::puzzle_rules();
+        assert_eq!(to_int("+150"), None);
+        assert!(rules.check("hgt", "+150cm").unwrap().is_err());
+        assert!(rules.check("byr", "+1980").unwrap().is_err());
+    }
+
+    #[test]
+    fn pid_is_exactly_nine_digits() {
+        let rules = Rules::puzzle_rules();
+        assert!(rules.check("pid", "012345678").unwrap().is_ok());
+        assert!(rules.check("pid", "12345678a").unwrap().is_err());
+        assert!(rules.check("pid", "0123456789").unwrap().is_err());
+    }
+
+    #[test]
+    fn hcl_regex_is_compiled_once_and_anchored() {
+        let rules = Rules::puzzle_rules();
+        let hcl = rules
+            .fields
+            .iter()
+            .find(|field| field.key == "hcl")
+            .unwrap();
+        match &hcl.check {
+            // compiled when the rules load, wrapped so it has to match the whole value
+            Check::Regex(regex, pattern) => {
+                assert_eq!(regex.as_str(), format!("^(?:{})$", pattern));
+            }
+            _ => panic!("hcl should be a regex rule"),
+        }
+        assert!(rules.check("hcl", "#123abc").unwrap().is_ok());
+        assert!(rules.check("hcl", "#123abcd").unwrap().is_err());
+        assert!(rules.check("hcl", "x#123abc").unwrap().is_err());
+    }
+
+    #[test]
+    fn unanchored_patterns_match_the_whole_value() {
+        let rules = Rules::from_json(
+            r#"{ "fields": [
+                { "key": "pid", "rule": { "regex": "[0-9]{9}" } },
+                { "key": "cid", "rule": { "regex": "[0-9]{9}" } }
+            ] }"#,
+        )
+        .unwrap();
+        assert!(rules.check("pid", "0123456789").unwrap().is_err());
+        assert!(rules.check("cid", "a012345678").unwrap().is_err());
+        // both fields get the same anchored regex from the cache, not a new pattern each
+        let regexes: Vec<&Regex> = rules
+            .fields
+            .iter()
+            .filter_map(|field| match &field.check {
+                Check::Regex(regex, _) => Some(regex),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(regexes.len(), 2);
+        assert_eq!(regexes[0].as_str(), regexes[1].as_str());
+    }
+}
@@ -0,0 +1,149 @@
+use crate::passport::Passport;
+use crate::report::PassportReport;
+use crate::rules::{to_int, Rules};
+use std::collections::BTreeMap;
+
+// how many times a rule was checked, and how many of those failed
+#[derive(Default)]
+struct RuleCount {
+    checked: usize,
+    failed: usize,
+}
+
+// totals across a whole batch of passports.
+// everything here grows with the number of distinct values, not the number of passports
+#[derive(Default)]
+pub struct Stats {
+    total: usize,
+    missing: BTreeMap<String, usize>,
+    // year -> count, for byr, iyr and eyr
+    years: BTreeMap<&'static str, BTreeMap<u32, usize>>,
+    // years that weren't numbers at all
+    non_numeric_years: BTreeMap<&'static str, usize>,
+    heights_cm: usize,
+    heights_in: usize,
+    heights_other: usize,
+    eye_colors: BTreeMap<String, usize>,
+    rules: BTreeMap<String, RuleCount>,
+}
+
+const YEAR_FIELDS: [(&str, &str); 3] = [
+    ("byr", "Birth years"),
+    ("iyr", "Issue years"),
+    ("eyr", "Expiration years"),
+];
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 * 100.0 / total as f64
+    }
+}
+
+impl Stats {
+    pub fn add(&mut self, passport: &Passport, report: &PassportReport, rules: &Rules) {
+        self.total += 1;
+
+        for key in report.missing.iter() {
+            *self.missing.entry(key.clone()).or_insert(0) += 1;
+        }
+
+        for &(key, _) in YEAR_FIELDS.iter() {
+            match passport.get(key).map(to_int) {
+                Some(Some(year)) => {
+                    *self.years.entry(key).or_default().entry(year).or_insert(0) += 1
+                }
+                Some(None) => *self.non_numeric_years.entry(key).or_insert(0) += 1,
+                None => {}
+            }
+        }
+
+        match passport.hgt.as_deref() {
+            Some(hgt) if hgt.ends_with("cm") => self.heights_cm += 1,
+            Some(hgt) if hgt.ends_with("in") => self.heights_in += 1,
+            Some(_) => self.heights_other += 1,
+            None => {}
+        }
+
+        if let Some(ecl) = passport.ecl.as_deref() {
+            *self.eye_colors.entry(ecl.to_string()).or_insert(0) += 1;
+        }
+
+        // every field that has a rule got checked, and the report knows which ones failed
+        for (key, _) in passport.fields() {
+            if rules.has_rule(key) {
+                self.rules.entry(key.to_string()).or_default().checked += 1;
+            }
+        }
+        for field in report.invalid.iter() {
+            self.rules.entry(field.key.clone()).or_default().failed += 1;
+        }
+    }
+
+    pub fn print(&self, rules: &Rules) {
+        println!("Across {} passports:", self.total);
+
+        println!("\nMissing fields");
+        for key in rules.required_keys() {
+            let missing = self.missing.get(key).copied().unwrap_or(0);
+            println!(
+                "  {:<8} {:>6} ({:.1}%)",
+                key,
+                missing,
+                percent(missing, self.total)
+            );
+        }
+
+        for &(key, title) in YEAR_FIELDS.iter() {
+            println!("\n{} ({})", title, key);
+            if let Some(years) = self.years.get(key) {
+                for (year, count) in years.iter() {
+                    println!("  {:<8} {:>6}", year, count);
+                }
+            }
+            if let Some(count) = self.non_numeric_years.get(key) {
+                println!("  {:<8} {:>6}", "other", count);
+            }
+        }
+
+        let heights = self.heights_cm + self.heights_in + self.heights_other;
+        println!("\nHeights (hgt)");
+        for (unit, count) in [
+            ("cm", self.heights_cm),
+            ("in", self.heights_in),
+            ("other", self.heights_other),
+        ] {
+            println!(
+                "  {:<8} {:>6} ({:.1}%)",
+                unit,
+                count,
+                percent(count, heights)
+            );
+        }
+
+        let eye_colors: usize = self.eye_colors.values().sum();
+        println!("\nEye colors (ecl)");
+        for (ecl, count) in self.eye_colors.iter() {
+            println!(
+                "  {:<8} {:>6} ({:.1}%)",
+                ecl,
+                count,
+                percent(*count, eye_colors)
+            );
+        }
+
+        println!("\nRule failures");
+        for key in rules.required_keys() {
+            if let Some(rule) = self.rules.get(key) {
+                println!(
+                    "  {:<8} {:>6} of {} checked ({:.1}%)",
+                    key,
+                    rule.failed,
+                    rule.checked,
+                    percent(rule.failed, rule.checked)
+                );
+            }
+        }
+    }
+}
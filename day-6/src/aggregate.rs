@@ -0,0 +1,98 @@
+use crate::answers::{AnswerSet, Questions};
+
+// how often each question was answered, across every group
+pub struct Aggregate<'a> {
+    questions: &'a Questions,
+    people: usize,
+    groups: usize,
+    // indexed by question number
+    people_per_question: Vec<usize>,
+    groups_per_question: Vec<usize>,
+    // how many people answered both questions.
+    // the diagonal is just how many people answered that question
+    co_occurrence: Vec<Vec<usize>>,
+}
+
+// quote a CSV value if it has anything that would break the row apart
+fn csv_value(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl<'a> Aggregate<'a> {
+    pub fn from_survey(questions: &'a Questions, groups: &[Vec<AnswerSet>]) -> Aggregate<'a> {
+        let question_count = questions.len();
+        let mut aggregate = Aggregate {
+            questions,
+            people: 0,
+            groups: groups.len(),
+            people_per_question: vec![0; question_count],
+            groups_per_question: vec![0; question_count],
+            co_occurrence: vec![vec![0; question_count]; question_count],
+        };
+
+        for group in groups.iter() {
+            let mut group_answers = AnswerSet::empty();
+            for person_answers in group.iter() {
+                aggregate.people += 1;
+                for answer in person_answers.iter() {
+                    aggregate.people_per_question[answer] += 1;
+                    for other_answer in person_answers.iter() {
+                        aggregate.co_occurrence[answer][other_answer] += 1;
+                    }
+                }
+                group_answers = group_answers.union(person_answers);
+            }
+            for answer in group_answers.iter() {
+                aggregate.groups_per_question[answer] += 1;
+            }
+        }
+        aggregate
+    }
+
+    // one row per question, most popular with people first
+    pub fn print_frequencies_csv(&self) {
+        let share = |count: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                count as f64 / total as f64
+            }
+        };
+        let mut questions: Vec<usize> = (0..self.questions.len()).collect();
+        // stable sort, so ties stay in question order
+        questions.sort_by_key(|&question| std::cmp::Reverse(self.people_per_question[question]));
+
+        println!("question,people,people_share,groups,groups_share");
+        for question in questions {
+            let people = self.people_per_question[question];
+            let groups = self.groups_per_question[question];
+            println!(
+                "{},{},{:.4},{},{:.4}",
+                csv_value(self.questions.id(question)),
+                people,
+                share(people, self.people),
+                groups,
+                share(groups, self.groups)
+            );
+        }
+    }
+
+    pub fn print_co_occurrence_csv(&self) {
+        let header: Vec<String> = (0..self.questions.len())
+            .map(|question| csv_value(self.questions.id(question)))
+            .collect();
+        println!(",{}", header.join(","));
+        for (question, counts) in self.co_occurrence.iter().enumerate() {
+            let row: Vec<String> = counts.iter().map(|count| count.to_string()).collect();
+            println!(
+                "{},{}",
+                csv_value(self.questions.id(question)),
+                row.join(",")
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+// every question anyone answered, so sets can just hold a number per question.
+// questions are numbered in the order we first saw them
+pub struct Questions {
+    ids: Vec<String>,
+    indexes: HashMap<String, usize>,
+    // what goes between question ids when we write a set out
+    separator: &'static str,
+}
+
+impl Questions {
+    // the puzzle's questions, a to z, written out next to each other like "abc".
+    // they're all added up front so a is always 0 and sets come out alphabetical,
+    // which lets letter_index skip the lookup entirely
+    pub fn letters() -> Questions {
+        let mut questions = Questions {
+            ids: vec![],
+            indexes: HashMap::new(),
+            separator: "",
+        };
+        for letter in 'a'..='z' {
+            questions.intern(&letter.to_string());
+        }
+        questions
+    }
+
+    // the question number for a letter from a to z, when we're using letters()
+    pub fn letter_index(letter: char) -> usize {
+        letter as usize - 'a' as usize
+    }
+
+    // questions with ids of any length, written out like "q1,q2,q10"
+    pub fn ids() -> Questions {
+        Questions {
+            ids: vec![],
+            indexes: HashMap::new(),
+            separator: ",",
+        }
+    }
+
+    // the number for this question, adding it if we haven't seen it before
+    pub fn intern(&mut self, id: &str) -> usize {
+        if let Some(&index) = self.indexes.get(id) {
+            return index;
+        }
+        self.ids.push(id.to_string());
+        self.indexes.insert(id.to_string(), self.ids.len() - 1);
+        self.ids.len() - 1
+    }
+
+    pub fn id(&self, index: usize) -> &str {
+        &self.ids[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn format(&self, answers: &AnswerSet) -> String {
+        let ids: Vec<&str> = answers.iter().map(|index| self.id(index)).collect();
+        ids.join(self.separator)
+    }
+}
+
+// a set of questions, one bit per question number.
+// the first 64 questions live inline, so letters (and most surveys) never touch the heap;
+// only question numbers past that spill over into a Vec
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct AnswerSet {
+    low: u64,
+    high: Vec<u64>,
+}
+
+impl AnswerSet {
+    pub fn empty() -> AnswerSet {
+        AnswerSet::default()
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        if index < 64 {
+            self.low |= 1 << index;
+            return;
+        }
+        let word = index / 64 - 1;
+        if self.high.len() <= word {
+            self.high.resize(word + 1, 0);
+        }
+        self.high[word] |= 1 << (index % 64);
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        if index < 64 {
+            return self.low & 1 << index != 0;
+        }
+        self.high
+            .get(index / 64 - 1)
+            .is_some_and(|bits| bits & 1 << (index % 64) != 0)
+    }
+
+    // takes self, so folding over a group reuses the same set instead of making new ones
+    pub fn union(mut self, other: &AnswerSet) -> AnswerSet {
+        self.low |= other.low;
+        if self.high.len() < other.high.len() {
+            self.high.resize(other.high.len(), 0);
+        }
+        for (bits, other_bits) in self.high.iter_mut().zip(other.high.iter()) {
+            *bits |= other_bits;
+        }
+        self
+    }
+
+    pub fn intersection(mut self, other: &AnswerSet) -> AnswerSet {
+        self.low &= other.low;
+        self.high.truncate(other.high.len());
+        for (bits, other_bits) in self.high.iter_mut().zip(other.high.iter()) {
+            *bits &= other_bits;
+        }
+        self
+    }
+
+    pub fn len(&self) -> u32 {
+        self.low.count_ones() + self.high.iter().map(|bits| bits.count_ones()).sum::<u32>()
+    }
+
+    // the question numbers in the set, lowest first
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.low)
+            .chain(self.high.iter().copied())
+            .enumerate()
+            .flat_map(|(word, bits)| {
+                // pop the lowest bit off until there's none left
+                std::iter::successors((bits != 0).then_some(bits), |&bits| {
+                    let rest = bits & (bits - 1);
+                    (rest != 0).then_some(rest)
+                })
+                .map(move |bits| word * 64 + bits.trailing_zeros() as usize)
+            })
+    }
+}
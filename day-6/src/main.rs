@@ -1,76 +1,411 @@
-use array_tool::vec::Intersect;
-use std::collections::HashSet;
+mod aggregate;
+mod answers;
+
+use aggregate::Aggregate;
+use answers::{AnswerSet, Questions};
+use serde::Serialize;
+use std::env;
+use std::fmt;
 use std::fs::read_to_string;
+use std::process;
 
-fn format_groups_flattened(input_file: &str) -> Vec<String> {
-    input_file
-        .split("\n\n") // split up the groups
-        .map(|s| s.replace('\n', "").to_string())
-        .collect()
+const USAGE: &str = "usage: day-6 [ANSWERS_FILE] [--ids] [--report table|json] \
+                     [--at-least PEOPLE | --fraction 0..1] [--aggregate frequencies|co-occurrence]";
+
+enum ReportFormat {
+    Table,
+    Json,
 }
 
-fn format_groups(input_file: &str) -> Vec<Vec<&str>> {
-    input_file
-        .split("\n\n") // split up the groups
-        .map(|group| {
-            group
-                .split('\n') // create vec of individual answers
-                .collect()
-        })
-        .collect()
+// totals across every group, as CSV
+enum AggregateFormat {
+    // how many people and groups answered each question
+    Frequencies,
+    // how many people answered each pair of questions together
+    CoOccurrence,
 }
 
-fn count_unique_answers(group_answers: &str) -> u32 {
-    group_answers
-        .chars()
-        .fold(HashSet::new(), |mut unique_answers, answer| {
-            unique_answers.insert(answer);
-            unique_answers
-        }) // generate Set of answers to remove duplicates
-        .len() as u32
+// how many people in a group need to answer a question for it to count.
+// AtLeast(1) is part 1 ("anyone"), and Fraction(1.0) is part 2 ("everyone")
+enum Threshold {
+    AtLeast(usize),
+    // a share of the group, between 0 (exclusive) and 1
+    Fraction(f64),
 }
 
-fn count_unanimous_answers(group_answers: &Vec<&str>) -> u32 {
-    if group_answers.len() == 0 {
-        0
-    } else {
-        // assume the first group's answers are unanimous among all groups
-        let starting_answers: Vec<char> = group_answers[0].chars().collect();
-        group_answers
-            .iter() // then, iterate through the groups to eliminate disagreements
-            .fold(starting_answers, |unanimous_answers, person_answers| {
-                // the intersection of all unanimous answers and a single group's answers
-                // should eliminate answers the group didn't agree with
-                unanimous_answers.intersect(person_answers.chars().collect())
-            })
-            .len() as u32
+impl Threshold {
+    fn min_people(&self, people: usize) -> usize {
+        match *self {
+            Threshold::AtLeast(min_people) => min_people,
+            // nudge down a hair so 0.7 of 10 people is 7, not 8 from float rounding
+            Threshold::Fraction(fraction) => {
+                ((fraction * people as f64 - 1e-9).ceil() as usize).max(1)
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match *self {
+            Threshold::AtLeast(1) => "at least 1 person".to_string(),
+            Threshold::AtLeast(min_people) => format!("at least {} people", min_people),
+            // one decimal place, so 0.07 doesn't come out as 7.000000000000001%
+            Threshold::Fraction(fraction) => {
+                format!("at least {:.1}% of a group", fraction * 100.0)
+            }
+        }
     }
 }
 
-fn main() {
-    let input_file = read_to_string("group_answers.txt");
+struct Options {
+    answers_file: String,
+    // each person's line is a comma-separated list of question ids, instead of letters
+    ids: bool,
+    // print what every group answered, not just the totals
+    report: Option<ReportFormat>,
+    // also count the answers given by some of each group
+    threshold: Option<Threshold>,
+    aggregate: Option<AggregateFormat>,
+}
 
-    match input_file {
-        Ok(input_file) => {
-            let part_1 = format_groups_flattened(&input_file)
-                .iter()
-                .fold(0, |answer_count, group| {
-                    answer_count + count_unique_answers(group)
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut answers_file = None;
+    let mut ids = false;
+    let mut report = None;
+    let mut threshold = None;
+    let mut aggregate = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ids" => ids = true,
+            "--report" => match args.next().as_deref() {
+                Some("table") => report = Some(ReportFormat::Table),
+                Some("json") => report = Some(ReportFormat::Json),
+                _ => return Err("--report needs to be table or json".to_string()),
+            },
+            "--at-least" => match args.next().map(|raw| raw.parse::<usize>()) {
+                Some(Ok(min_people)) if min_people > 0 => {
+                    threshold = Some(Threshold::AtLeast(min_people))
+                }
+                _ => return Err("--at-least needs a number of people above 0".to_string()),
+            },
+            "--fraction" => match args.next().map(|raw| raw.parse::<f64>()) {
+                Some(Ok(fraction)) if fraction > 0.0 && fraction <= 1.0 => {
+                    threshold = Some(Threshold::Fraction(fraction))
+                }
+                _ => return Err("--fraction needs a number above 0, up to 1".to_string()),
+            },
+            "--aggregate" => match args.next().as_deref() {
+                Some("frequencies") => aggregate = Some(AggregateFormat::Frequencies),
+                Some("co-occurrence") => aggregate = Some(AggregateFormat::CoOccurrence),
+                _ => return Err("--aggregate needs to be frequencies or co-occurrence".to_string()),
+            },
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            path => {
+                if answers_file.is_some() {
+                    return Err(format!("unexpected argument {}", path));
+                }
+                answers_file = Some(path.to_string());
+            }
+        }
+    }
+
+    if report.is_some() && aggregate.is_some() {
+        return Err("pick either --report or --aggregate, not both".to_string());
+    }
+
+    Ok(Options {
+        answers_file: answers_file.unwrap_or_else(|| "group_answers.txt".to_string()),
+        ids,
+        report,
+        threshold,
+        aggregate,
+    })
+}
+
+// what a single group answered
+#[derive(Serialize)]
+struct GroupReport {
+    // which group this is in the file, starting from 1
+    index: usize,
+    people: usize,
+    // questions anyone in the group answered "yes" to
+    anyone: String,
+    // questions everyone in the group answered "yes" to
+    everyone: String,
+    // questions enough of the group answered "yes" to, if we were asked for a threshold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    at_threshold: Option<String>,
+}
+
+// an answer we couldn't make sense of
+#[derive(Debug)]
+struct ParseError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+// every group's answers, with the questions they refer to
+struct Survey {
+    questions: Questions,
+    // each group is a list of each person's answers
+    groups: Vec<Vec<AnswerSet>>,
+}
+
+// a person's answers as letters, like "abc". anything outside a to z is an error,
+// and returns the column (counted from 0 in the trimmed line) and what we found there
+// (no lookups needed, Questions::letters() already has every letter in order)
+fn parse_letters(person_answers: &str) -> Result<AnswerSet, (usize, String)> {
+    let mut answers = AnswerSet::empty();
+    for (column, answer) in person_answers.chars().enumerate() {
+        if !answer.is_ascii_lowercase() {
+            return Err((column, format!("{:?} isn't a question from a to z", answer)));
+        }
+        answers.insert(Questions::letter_index(answer));
+    }
+    Ok(answers)
+}
+
+// a person's answers as question ids, like "q1, q2,q10"
+fn parse_ids(
+    person_answers: &str,
+    questions: &mut Questions,
+) -> Result<AnswerSet, (usize, String)> {
+    let mut answers = AnswerSet::empty();
+    let mut column = 0;
+    for id in person_answers.split(',') {
+        if id.trim().is_empty() {
+            return Err((column, "there's an empty question id".to_string()));
+        }
+        answers.insert(questions.intern(id.trim()));
+        column += id.chars().count() + 1;
+    }
+    Ok(answers)
+}
+
+// splits the file into groups, and each group into the answers of each person.
+// groups are separated by lines that are blank (or only spaces),
+// and \r\n line endings or extra newlines at the end don't add empty people
+fn format_groups(
+    input_file: &str,
+    mut questions: Questions,
+    ids: bool,
+) -> Result<Survey, ParseError> {
+    let mut groups = vec![];
+    let mut curr_group = vec![];
+
+    for (line_index, line) in input_file.lines().enumerate() {
+        let person_answers = line.trim();
+        if person_answers.is_empty() {
+            // a blank line means the group we were reading is done
+            if !curr_group.is_empty() {
+                groups.push(curr_group);
+                curr_group = vec![];
+            }
+            continue;
+        }
+
+        let parsed = if ids {
+            parse_ids(person_answers, &mut questions)
+        } else {
+            parse_letters(person_answers)
+        };
+        match parsed {
+            Ok(answers) => curr_group.push(answers),
+            Err((column, message)) => {
+                // count the column from the start of the line, before we trimmed it
+                let leading_space = line.chars().count() - line.trim_start().chars().count();
+                return Err(ParseError {
+                    line: line_index + 1,
+                    column: leading_space + column + 1,
+                    message,
                 });
+            }
+        }
+    }
+
+    // the file might not end with a blank line
+    if !curr_group.is_empty() {
+        groups.push(curr_group);
+    }
+    Ok(Survey { questions, groups })
+}
+
+fn unique_answers(group_answers: &[AnswerSet]) -> AnswerSet {
+    group_answers
+        .iter()
+        // any answer from anyone in the group counts
+        .fold(AnswerSet::empty(), |unique_answers, person_answers| {
+            unique_answers.union(person_answers)
+        })
+}
+
+fn count_unique_answers(group_answers: &[AnswerSet]) -> u32 {
+    unique_answers(group_answers).len()
+}
 
-            let part_2 = format_groups(&input_file)
+fn unanimous_answers(group_answers: &[AnswerSet]) -> AnswerSet {
+    match group_answers.split_first() {
+        None => AnswerSet::empty(),
+        // assume the first person's answers are unanimous, then eliminate disagreements
+        Some((first_person, others)) => {
+            others
                 .iter()
-                .fold(0, |unanimous_count, group| {
-                    unanimous_count + count_unanimous_answers(group)
-                });
+                .fold(first_person.clone(), |unanimous_answers, person_answers| {
+                    // the intersection of all unanimous answers and a single person's answers
+                    // should eliminate answers the person didn't agree with
+                    unanimous_answers.intersection(person_answers)
+                })
+        }
+    }
+}
+
+fn count_unanimous_answers(group_answers: &[AnswerSet]) -> u32 {
+    unanimous_answers(group_answers).len()
+}
+
+// the questions answered by at least threshold.min_people() people in the group
+fn answers_at_threshold(group_answers: &[AnswerSet], threshold: &Threshold) -> AnswerSet {
+    let min_people = threshold.min_people(group_answers.len());
+    // only questions somebody answered can make the cut,
+    // so count how many people answered each of those
+    let mut answers = AnswerSet::empty();
+    for answer in unique_answers(group_answers).iter() {
+        let people = group_answers
+            .iter()
+            .filter(|person_answers| person_answers.contains(answer))
+            .count();
+        if people >= min_people {
+            answers.insert(answer);
+        }
+    }
+    answers
+}
+
+fn count_answers_at_threshold(group_answers: &[AnswerSet], threshold: &Threshold) -> u32 {
+    answers_at_threshold(group_answers, threshold).len()
+}
+
+fn report_groups(survey: &Survey, threshold: Option<&Threshold>) -> Vec<GroupReport> {
+    let questions = &survey.questions;
+    survey
+        .groups
+        .iter()
+        .enumerate()
+        .map(|(index, group)| GroupReport {
+            index: index + 1,
+            people: group.len(),
+            anyone: questions.format(&unique_answers(group)),
+            everyone: questions.format(&unanimous_answers(group)),
+            at_threshold: threshold
+                .map(|threshold| questions.format(&answers_at_threshold(group, threshold))),
+        })
+        .collect()
+}
 
-            println!(
-                "Among the groups, there's \n\
-                - {} total answers \n\
-                - {} unanimous answers",
-                part_1, part_2
-            )
+fn print_table(reports: &[GroupReport]) {
+    println!(
+        "{:>6}  {:>6}  {:<26}  {:<26}  at threshold",
+        "group", "people", "anyone", "everyone"
+    );
+    for report in reports {
+        println!(
+            "{:>6}  {:>6}  {:<26}  {:<26}  {}",
+            report.index,
+            report.people,
+            report.anyone,
+            report.everyone,
+            report.at_threshold.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+
+    let input_file = match read_to_string(&options.answers_file) {
+        Ok(input_file) => input_file,
+        Err(e) => {
+            eprintln!("Something's wrong with this input file: {}", e);
+            process::exit(1);
+        }
+    };
+    let questions = if options.ids {
+        Questions::ids()
+    } else {
+        Questions::letters()
+    };
+    let survey = match format_groups(&input_file, questions, options.ids) {
+        Ok(survey) => survey,
+        Err(e) => {
+            eprintln!("Something's wrong with these answers: {}", e);
+            process::exit(1);
         }
-        Err(_) => println!("Something's wrong with this input file!"),
     };
+
+    if let Some(format) = &options.aggregate {
+        let aggregate = Aggregate::from_survey(&survey.questions, &survey.groups);
+        match format {
+            AggregateFormat::Frequencies => aggregate.print_frequencies_csv(),
+            AggregateFormat::CoOccurrence => aggregate.print_co_occurrence_csv(),
+        }
+        // keep stdout as valid CSV
+        return;
+    }
+
+    match options.report {
+        Some(ReportFormat::Table) => {
+            print_table(&report_groups(&survey, options.threshold.as_ref()))
+        }
+        Some(ReportFormat::Json) => {
+            let reports = report_groups(&survey, options.threshold.as_ref());
+            println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+            // keep stdout as valid JSON
+            return;
+        }
+        None => {}
+    }
+
+    let part_1 = survey.groups.iter().fold(0, |answer_count, group| {
+        answer_count + count_unique_answers(group)
+    });
+
+    let part_2 = survey.groups.iter().fold(0, |unanimous_count, group| {
+        unanimous_count + count_unanimous_answers(group)
+    });
+
+    println!(
+        "Among the groups, there's \n\
+        - {} total answers \n\
+        - {} unanimous answers",
+        part_1, part_2
+    );
+
+    if let Some(threshold) = &options.threshold {
+        let at_threshold = survey.groups.iter().fold(0, |answer_count, group| {
+            answer_count + count_answers_at_threshold(group, threshold)
+        });
+        println!(
+            "- {} answers given by {}",
+            at_threshold,
+            threshold.describe()
+        );
+    }
 }
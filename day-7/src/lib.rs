@@ -0,0 +1,164 @@
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// a line that doesn't look like "light red bags contain 1 bright white bag, ..."
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub text: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} is poorly formatted: {}", self.line, self.text)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// why we couldn't count the bags inside a bag
+#[derive(Debug, PartialEq)]
+pub enum CountError {
+    // the rules loop back around, so a bag ends up inside itself and there's no end to it
+    Cycle,
+    // more bags than fit in a u64
+    Overflow,
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CountError::Cycle => write!(f, "the rules loop back around, so there's no end to it"),
+            CountError::Overflow => write!(f, "there's too many bags to count"),
+        }
+    }
+}
+
+impl std::error::Error for CountError {}
+
+// every rule about which bags go inside which, with edges both ways
+// so we can ask what's inside a bag or what a bag can end up inside
+#[derive(Default)]
+pub struct BagRules {
+    // bag -> (how many, child bag), in the order the rule lists them
+    children: HashMap<String, Vec<(u64, String)>>,
+    // bag -> (how many, parent bag), for every rule that mentions it
+    parents: HashMap<String, Vec<(u64, String)>>,
+}
+
+impl BagRules {
+    pub fn parse(raw: &str) -> Result<BagRules, ParseError> {
+        let get_bag_info = Regex::new(r"([0-9]+) (\w+ \w+) bags?").unwrap();
+        // assumes the color will be stated first, with 2 words
+        let get_first_bag_color = Regex::new(r"^(\w+ \w+) bags contain ").unwrap();
+
+        let mut rules = BagRules::default();
+        for (line_index, line) in raw.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let first_bag_color = match get_first_bag_color.captures(line) {
+                Some(first_bag_color_captures) => first_bag_color_captures[1].to_string(),
+                None => {
+                    return Err(ParseError {
+                        line: line_index + 1,
+                        text: line.to_string(),
+                    })
+                }
+            };
+            // a bag that "contains no other bags" still gets an entry
+            let child_bags = rules.children.entry(first_bag_color.clone()).or_default();
+            for bag_info_capture in get_bag_info.captures_iter(line) {
+                // the regex only matches digits, so this is always a number
+                let number: u64 = bag_info_capture[1].parse().unwrap();
+                let color = bag_info_capture[2].to_string();
+
+                child_bags.push((number, color.clone()));
+                rules
+                    .parents
+                    .entry(color)
+                    .or_default()
+                    .push((number, first_bag_color.clone()));
+            }
+        }
+        Ok(rules)
+    }
+
+    // true if there's a rule saying what goes inside this bag
+    pub fn has_rule(&self, color: &str) -> bool {
+        self.children.contains_key(color)
+    }
+
+    // every bag color with a rule of its own
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.children.keys().map(String::as_str)
+    }
+
+    // the bags that go straight inside this one, and how many of each
+    pub fn direct_children(&self, color: &str) -> &[(u64, String)] {
+        self.children.get(color).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // the bags this one goes straight inside, and how many of it each one holds
+    pub fn direct_parents(&self, color: &str) -> &[(u64, String)] {
+        self.parents.get(color).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // every bag that can eventually contain this one (part 1)
+    pub fn ancestors(&self, color: &str) -> HashSet<&str> {
+        reachable(color, |bag| self.direct_parents(bag))
+    }
+
+    // every bag that can eventually end up inside this one
+    pub fn descendants(&self, color: &str) -> HashSet<&str> {
+        reachable(color, |bag| self.direct_children(bag))
+    }
+
+    // how many bags end up inside this one, counting the bags inside those bags too (part 2)
+    pub fn contains_count(&self, color: &str) -> Result<u64, CountError> {
+        self.count_contained_bags(color, &mut HashMap::new())
+    }
+
+    // lots of bags share the same children, so each bag's total is only worked out once.
+    // a bag is marked None while we're inside it, so running into it again means a loop
+    fn count_contained_bags<'a>(
+        &'a self,
+        curr: &'a str,
+        contained_counts: &mut HashMap<&'a str, Option<u64>>,
+    ) -> Result<u64, CountError> {
+        match contained_counts.get(curr) {
+            Some(&Some(count)) => return Ok(count),
+            Some(None) => return Err(CountError::Cycle),
+            None => {}
+        }
+        contained_counts.insert(curr, None);
+        let mut count: u64 = 0;
+        for (number, child_bag) in self.direct_children(curr) {
+            // each child bag, plus everything inside each of them
+            let inside_child = self.count_contained_bags(child_bag, contained_counts)?;
+            count = inside_child
+                .checked_add(1)
+                .and_then(|per_child| per_child.checked_mul(*number))
+                .and_then(|children| count.checked_add(children))
+                .ok_or(CountError::Overflow)?;
+        }
+        contained_counts.insert(curr, Some(count));
+        Ok(count)
+    }
+}
+
+// every bag we can get to by following edges out from start, not counting start itself,
+// even when the rules loop back around to it
+fn reachable<'a>(start: &str, edges: impl Fn(&str) -> &'a [(u64, String)]) -> HashSet<&'a str> {
+    let mut visited_bags = HashSet::new();
+    let mut to_visit: Vec<&str> = edges(start).iter().map(|(_, bag)| bag.as_str()).collect();
+    while let Some(bag) = to_visit.pop() {
+        if visited_bags.insert(bag) {
+            to_visit.extend(edges(bag).iter().map(|(_, next_bag)| next_bag.as_str()));
+        }
+    }
+    visited_bags.remove(start);
+    visited_bags
+}
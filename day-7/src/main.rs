@@ -1,72 +1,91 @@
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use day_7::{BagRules, CountError};
+use std::env;
 use std::fs::read_to_string;
+use std::process;
 
-// match: ([0-9]) ([a-z]* [a-z]*) bags?]*
-fn split_lines(raw: &str) -> Vec<&str> {
-    raw.split('\n').collect()
+const USAGE: &str = "usage: day-7 [BAGS_FILE] [--color COLOR]";
+
+struct Options {
+    bags_file: String,
+    // the bag we're asking about, like "shiny gold"
+    color: String,
 }
 
-fn traverse(
-    curr: &str,
-    visited_bags: &HashSet<String>,
-    bag_to_parents_map: &HashMap<String, HashSet<String>>,
-) -> HashSet<String> {
-    match bag_to_parents_map.get(curr) {
-        Some(parent_bags) => {
-            let mut visited_bags = parent_bags.difference(&visited_bags).fold(
-                HashSet::new(),
-                |visited_bags, parent_bag| {
-                    traverse(parent_bag, &visited_bags, bag_to_parents_map)
-                        .union(&visited_bags)
-                        .cloned()
-                        .collect()
-                },
-            );
-            visited_bags.insert(curr.to_string());
-            visited_bags
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut bags_file = None;
+    let mut color = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--color" => match args.next() {
+                Some(bag_color) if !bag_color.trim().is_empty() => {
+                    color = Some(bag_color.trim().to_string())
+                }
+                _ => return Err("--color needs a bag color, like \"shiny gold\"".to_string()),
+            },
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            path => {
+                if bags_file.is_some() {
+                    return Err(format!("unexpected argument {}", path));
+                }
+                bags_file = Some(path.to_string());
+            }
         }
-        None => [curr.to_string()].iter().cloned().collect(),
     }
+
+    Ok(Options {
+        bags_file: bags_file.unwrap_or_else(|| "bags.txt".to_string()),
+        color: color.unwrap_or_else(|| "shiny gold".to_string()),
+    })
 }
 
 fn main() {
-    let get_bag_info = Regex::new(r"([0-9]) (\w* \w*) bags?").unwrap();
-    // assumes the color will be stated first, with 2 words
-    let get_first_bag_color = Regex::new(r"^\w+ \w+").unwrap();
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
 
-    let raw_input = read_to_string("bags.txt");
-    match raw_input {
-        Ok(raw_input) => {
-            let lines = split_lines(&raw_input);
-            let mut bag_to_parents_map: HashMap<String, HashSet<String>> = HashMap::new();
-            for line in lines {
-                match get_first_bag_color.captures(line) {
-                    Some(first_bag_color_captures) => {
-                        let first_bag_color = &first_bag_color_captures[0];
-                        for bag_info_capture in get_bag_info.captures_iter(line) {
-                            let number = &bag_info_capture[1];
-                            let color = &bag_info_capture[2];
+    let raw_input = match read_to_string(&options.bags_file) {
+        Ok(raw_input) => raw_input,
+        Err(e) => {
+            eprintln!("Something's wrong with the input file: {}", e);
+            process::exit(1);
+        }
+    };
+    let bag_rules = match BagRules::parse(&raw_input) {
+        Ok(bag_rules) => bag_rules,
+        Err(e) => {
+            eprintln!("Something's wrong with these rules: {}", e);
+            process::exit(1);
+        }
+    };
 
-                            bag_to_parents_map
-                                // find the right bag in our Map
-                                .entry(color.to_string())
-                                // if we can't find it,
-                                // create the key with an empty Set as our value
-                                .or_insert(HashSet::new())
-                                // otherwise, append to the existing Set at that key
-                                .insert(first_bag_color.to_string());
-                        }
-                    }
-                    None => println!("This line is poorly formatted \n {}", line),
-                }
-            }
-            println!("{:?}", bag_to_parents_map);
-            println!(
-                "{}",
-                traverse("shiny gold", &HashSet::new(), &bag_to_parents_map).len() - 1
+    // a typo in --color would otherwise look like a bag that's in nothing and holds nothing
+    if !bag_rules.has_rule(&options.color) && bag_rules.direct_parents(&options.color).is_empty() {
+        eprintln!("There's no rule about {} bags", options.color);
+        process::exit(1);
+    }
+    println!(
+        "{} bags can eventually contain a {} bag",
+        bag_rules.ancestors(&options.color).len(),
+        options.color
+    );
+    match bag_rules.contains_count(&options.color) {
+        Ok(count) => println!("A {} bag contains {} other bags", options.color, count),
+        Err(CountError::Cycle) => {
+            eprintln!(
+                "A {} bag ends up inside itself, so there's no end to it",
+                options.color
             );
+            process::exit(1);
+        }
+        Err(CountError::Overflow) => {
+            eprintln!("A {} bag contains too many bags to count!", options.color);
+            process::exit(1);
         }
-        Err(_) => println!("Something's wrong with the input file!"),
     }
 }